@@ -1,56 +1,269 @@
-use std::{collections::HashMap, thread, time};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{BufReader, Read},
+    thread,
+    time::{self, SystemTime},
+};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Type {
     Gold,
     Silver,
     Bronze,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Gender {
+    Men,
+    Women,
+    Mixed,
+    Unknown,
+}
+
+#[derive(Debug, Clone)]
 struct Medal {
     r#type: Type,
     country: String,
+    // Title of the event the medal was awarded in; `None` if the feed gives
+    // the event no title. Like `fetched_at`, only read by the analysis
+    // helpers in the tests.
+    #[cfg_attr(not(test), allow(dead_code))]
+    event: Option<String>,
+    #[cfg_attr(not(test), allow(dead_code))]
+    gender: Gender,
+    // When the medal was seen in a live fetch; `None` when parsed from a
+    // static document. Only read by the analysis helpers in the tests.
+    #[cfg_attr(not(test), allow(dead_code))]
+    fetched_at: Option<SystemTime>,
 }
 
-// Determines the current list of (athletics) medals as advertised by olympics.com.
-fn fetch_medals() -> Result<Vec<Medal>, Box<dyn std::error::Error>> {
-    // From: https://olympics.com/en/olympic-games/tokyo-2020/results/athletics
-    let url =
-        "https://raw.githubusercontent.com/sekaiser/rust-sample-parse-json/main/athletics.json";
-    let json: serde_json::Value = reqwest::blocking::get(url)?.json()?;
-    let mut medals = vec![];
-    for event in json["pageProps"]["gameDiscipline"]["events"]
-        .as_array()
-        .unwrap()
-    {
-        for award in event["awards"].as_array().unwrap() {
-            let r#type = match award["medalType"].as_str().unwrap() {
-                "GOLD" => Type::Gold,
-                "SILVER" => Type::Silver,
-                "BRONZE" => Type::Bronze,
-                _ => panic!(),
-            };
-            let country = if !award["participant"]["countryObject"].is_object() {
-                award["participant"]["title"].as_str().unwrap()
-            } else {
-                award["participant"]["countryObject"]["name"]
-                    .as_str()
-                    .unwrap()
-            };
-            let medal = Medal {
-                r#type,
-                country: country.to_string(),
-            };
-            medals.push(medal);
+// Lists the keys `validate_schema` expected but did not find.
+#[derive(Debug, PartialEq, Eq)]
+struct SchemaError {
+    missing: Vec<String>,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "missing keys in medal JSON: {}", self.missing.join(", "))
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+// Key paths reported in `SchemaError::missing`, shared by `validate_schema`
+// and `stream_medals` so both name the same keys.
+const EVENTS_PATH: &str = "pageProps.gameDiscipline.events";
+
+fn awards_path(event_index: usize) -> String {
+    format!("{}[{}].awards", EVENTS_PATH, event_index)
+}
+
+// Checks that the keys the medal parser relies on are present, so a changed
+// feed is reported instead of silently producing wrong results.
+fn validate_schema(json: &serde_json::Value) -> Result<(), SchemaError> {
+    let mut missing = vec![];
+    if !json["pageProps"].is_object() {
+        missing.push("pageProps".to_string());
+    } else if !json["pageProps"]["gameDiscipline"].is_object() {
+        missing.push("pageProps.gameDiscipline".to_string());
+    } else {
+        match json["pageProps"]["gameDiscipline"]["events"].as_array() {
+            None => missing.push(EVENTS_PATH.to_string()),
+            Some(events) => {
+                for (i, event) in events.iter().enumerate() {
+                    if !event["awards"].is_array() {
+                        missing.push(awards_path(i));
+                    }
+                }
+            }
+        }
+    }
+
+    if missing.is_empty() {
+        Ok(())
+    } else {
+        Err(SchemaError { missing })
+    }
+}
+
+// Describes why medals could not be read from a document or award.
+#[derive(Debug)]
+enum ParseError {
+    Json(serde_json::Error),
+    Schema(SchemaError),
+    MissingField(&'static str),
+    UnknownMedalType(String),
+    // Only produced by the strict parser in the tests.
+    #[cfg_attr(not(test), allow(dead_code))]
+    UnexpectedField(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            // The underlying error is left to `source()` so that error
+            // chains do not print it twice.
+            ParseError::Json(_) => write!(f, "invalid medal JSON"),
+            ParseError::Schema(_) => write!(f, "unexpected medal JSON schema"),
+            ParseError::MissingField(key) => write!(f, "award is missing its {}", key),
+            ParseError::UnknownMedalType(name) => write!(f, "unknown medal type {:?}", name),
+            ParseError::UnexpectedField(key) => write!(f, "award has unexpected field {:?}", key),
         }
     }
+}
+
+impl std::error::Error for ParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseError::Json(err) => Some(err),
+            ParseError::Schema(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for ParseError {
+    fn from(err: serde_json::Error) -> Self {
+        ParseError::Json(err)
+    }
+}
+
+impl From<SchemaError> for ParseError {
+    fn from(err: SchemaError) -> Self {
+        ParseError::Schema(err)
+    }
+}
+
+// Yields the medals awarded in `json` one at a time, in document order. A
+// missing events or awards array is yielded as a `ParseError::Schema`
+// rather than skipped, so a changed feed never looks like an empty one.
+fn stream_medals(json: &serde_json::Value) -> impl Iterator<Item = Result<Medal, ParseError>> + '_ {
+    stream_awards(json).map(|item| item.and_then(|(event, award)| parse_award(event, award)))
+}
+
+// Yields each award together with the event it belongs to, or a
+// `ParseError::Schema` for a missing events or awards array.
+fn stream_awards(
+    json: &serde_json::Value,
+) -> impl Iterator<Item = Result<(&serde_json::Value, &serde_json::Value), ParseError>> + '_ {
+    let events = json["pageProps"]["gameDiscipline"]["events"].as_array();
+    let missing_events = events.is_none().then(|| {
+        Err(SchemaError {
+            missing: vec![EVENTS_PATH.to_string()],
+        }
+        .into())
+    });
+
+    let awards = events
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .flat_map(|(i, event)| {
+            let awards = event["awards"].as_array();
+            let missing_awards = awards.is_none().then(|| {
+                Err(SchemaError {
+                    missing: vec![awards_path(i)],
+                }
+                .into())
+            });
+            missing_awards.into_iter().chain(
+                awards
+                    .into_iter()
+                    .flatten()
+                    .map(move |award| Ok((event, award))),
+            )
+        });
+
+    missing_events.into_iter().chain(awards)
+}
+
+// The feed's name for each medal type.
+const MEDAL_TYPES: [(&str, Type); 3] = [
+    ("GOLD", Type::Gold),
+    ("SILVER", Type::Silver),
+    ("BRONZE", Type::Bronze),
+];
+
+// Whether `s` is one of the feed's medal type names (case-sensitive).
+fn is_valid_medal_type(s: &str) -> bool {
+    MEDAL_TYPES.iter().any(|(name, _)| *name == s)
+}
+
+fn parse_award(event: &serde_json::Value, award: &serde_json::Value) -> Result<Medal, ParseError> {
+    let name = award["medalType"]
+        .as_str()
+        .ok_or(ParseError::MissingField("medalType"))?;
+    if !is_valid_medal_type(name) {
+        return Err(ParseError::UnknownMedalType(name.to_string()));
+    }
+    let (_, r#type) = MEDAL_TYPES
+        .into_iter()
+        .find(|(spelling, _)| *spelling == name)
+        .expect("checked by is_valid_medal_type");
+    let country = if !award["participant"]["countryObject"].is_object() {
+        award["participant"]["title"].as_str()
+    } else {
+        award["participant"]["countryObject"]["name"].as_str()
+    }
+    .ok_or(ParseError::MissingField("country"))?;
+    let gender = match event["gender"].as_str() {
+        Some("Men") => Gender::Men,
+        Some("Women") => Gender::Women,
+        Some("Mixed") => Gender::Mixed,
+        _ => Gender::Unknown,
+    };
+    Ok(Medal {
+        r#type,
+        country: country.to_string(),
+        event: event["title"].as_str().map(str::to_string),
+        gender,
+        fetched_at: None,
+    })
+}
+
+// Validates and parses an already decoded medal document.
+fn parse_medals(json: &serde_json::Value) -> Result<Vec<Medal>, ParseError> {
+    validate_schema(json)?;
+    stream_medals(json).collect()
+}
+
+// Reads a whole medal document from `reader`, e.g. a file or an HTTP
+// response body. Wrap unbuffered readers in a `BufReader`.
+fn parse_medals_from_reader(reader: impl Read) -> Result<Vec<Medal>, ParseError> {
+    let json: serde_json::Value = serde_json::from_reader(reader)?;
+    parse_medals(&json)
+}
+
+// From: https://olympics.com/en/olympic-games/tokyo-2020/results/athletics
+const ATHLETICS_URL: &str =
+    "https://raw.githubusercontent.com/sekaiser/rust-sample-parse-json/main/athletics.json";
+
+// How long a single fetch may take before it is abandoned.
+const FETCH_TIMEOUT: time::Duration = time::Duration::from_secs(10);
+
+// Determines the current list of (athletics) medals as advertised by the
+// olympics.com results page mirrored at `url`.
+fn fetch_medals(
+    url: &str,
+    timeout: time::Duration,
+) -> Result<Vec<Medal>, Box<dyn std::error::Error>> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()?;
+    let response = client.get(url).send()?.error_for_status()?;
+    let mut medals = parse_medals_from_reader(BufReader::new(response))?;
+    let fetched_at = SystemTime::now();
+    for medal in &mut medals {
+        medal.fetched_at = Some(fetched_at);
+    }
     Ok(medals)
 }
 
 // Returns a list of (country, #gold, #silver, #bronze) tuples
 // ranked by medal count.
-fn create_table(medals: &Vec<Medal>) -> Vec<(String, usize, usize, usize)> {
+fn create_table(medals: impl IntoIterator<Item = Medal>) -> Vec<(String, usize, usize, usize)> {
     // Collect all medals a country has won
     let mut by_country = HashMap::new();
     for medal in medals {
@@ -89,8 +302,8 @@ fn main() {
     let mut last_top5 = None;
 
     loop {
-        let medals = fetch_medals().unwrap();
-        let table = create_table(&medals);
+        let medals = fetch_medals(ATHLETICS_URL, FETCH_TIMEOUT).unwrap();
+        let table = create_table(medals);
         let top5: Option<Vec<String>> = Some(table.iter().take(5).map(|e| e.0.clone()).collect());
         if top5 != last_top5 {
             println!("{:#?}", &top5);
@@ -103,14 +316,23 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use core::time;
+    use proptest::prelude::*;
     use reqwest::Url;
     use serde::Deserialize;
     use serde_json::Value;
     use std::collections::HashMap;
+    use std::fs::File;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
     use std::ops::Index;
     use std::ops::IndexMut;
     use std::str::FromStr;
     use std::thread;
+    use std::time::{Duration, SystemTime};
+
+    use super::{Gender, Medal, ParseError, SchemaError, Type, ATHLETICS_URL, FETCH_TIMEOUT};
+
+    const ATHLETICS_JSON: &str = include_str!("../athletics.json");
 
     #[derive(Debug, PartialEq, Eq, Deserialize)]
     enum Class {
@@ -129,6 +351,34 @@ mod tests {
         b: usize,
     }
 
+    impl MedalCount {
+        // Collapses the count into a single weighted score, e.g. 3/2/1 points
+        // per gold/silver/bronze.
+        fn dominance_score(&self, gold_weight: f64, silver_weight: f64, bronze_weight: f64) -> f64 {
+            self.g as f64 * gold_weight
+                + self.s as f64 * silver_weight
+                + self.b as f64 * bronze_weight
+        }
+
+        // Fraction of all medals that are gold, 0.0 if there are none.
+        fn ratio_gold_to_total(&self) -> f64 {
+            let total = self.g + self.s + self.b;
+            if total == 0 {
+                return 0.0;
+            }
+            self.g as f64 / total as f64
+        }
+
+        fn olympic_medal_value(&self) -> usize {
+            olympic_medal_value(self)
+        }
+    }
+
+    // Scores a count with the IOC's historical 3-2-1 points per gold/silver/bronze.
+    fn olympic_medal_value(count: &MedalCount) -> usize {
+        3 * count.g + 2 * count.s + count.b
+    }
+
     impl Index<Class> for MedalCount {
         type Output = usize;
 
@@ -154,6 +404,7 @@ mod tests {
     struct AthleticsDb(AthleticsDbInner);
 
     struct AthleticsDbInner {
+        url: Url,
         json: serde_json::Value,
     }
 
@@ -176,10 +427,37 @@ mod tests {
         values.iter().map(to_tuple).collect()
     }
 
+    fn fetch_json(url: &Url) -> Result<Value, Box<dyn std::error::Error>> {
+        Ok(reqwest::blocking::get(url.clone())?
+            .error_for_status()?
+            .json()?)
+    }
+
     impl AthleticsDb {
         pub fn from_url(url: Url) -> Result<Self, Box<dyn std::error::Error>> {
-            let json: serde_json::Value = reqwest::blocking::get(url)?.json()?;
-            Ok(AthleticsDb(AthleticsDbInner { json }))
+            let json = fetch_json(&url)?;
+            Ok(AthleticsDb(AthleticsDbInner { url, json }))
+        }
+
+        // Re-fetches the URL the db was created from. Returns whether the
+        // data differs from what was held before.
+        pub fn refresh(&mut self) -> Result<bool, Box<dyn std::error::Error>> {
+            let json = fetch_json(&self.0.url)?;
+            let changed = json != self.0.json;
+            self.0.json = json;
+            Ok(changed)
+        }
+
+        pub fn discipline_metadata(&self) -> DisciplineMetadata {
+            let discipline = &self.0.json["pageProps"]["gameDiscipline"];
+            let to_string = |v: &Value| v.as_str().map(str::to_string);
+
+            DisciplineMetadata {
+                name: to_string(&discipline["title"]).unwrap_or_default(),
+                start_date: to_string(&discipline["startDate"]),
+                end_date: to_string(&discipline["endDate"]),
+                event_count: discipline["events"].as_array().map_or(0, Vec::len),
+            }
         }
 
         pub fn get_medals_per_country(&self) -> Result<Projection, Box<dyn std::error::Error>> {
@@ -209,6 +487,14 @@ mod tests {
         }
     }
 
+    #[derive(Debug, PartialEq, Eq)]
+    struct DisciplineMetadata {
+        name: String,
+        start_date: Option<String>,
+        end_date: Option<String>,
+        event_count: usize,
+    }
+
     #[derive(Eq, PartialEq)]
     struct Projection(Vec<(String, MedalCount)>);
 
@@ -224,6 +510,210 @@ mod tests {
         fn get(&self) -> &Vec<(String, MedalCount)> {
             &self.0
         }
+
+        // Like `take`, but borrows the first `n` entries instead of consuming.
+        fn top_n(&self, n: usize) -> &[(String, MedalCount)] {
+            &self.0[..n.min(self.0.len())]
+        }
+
+        // Returns the 1-based rank of `country`, or `None` if it is not listed.
+        fn rank_of(&self, country: &str) -> Option<usize> {
+            self.0
+                .iter()
+                .position(|(name, _)| name == country)
+                .map(|i| i + 1)
+        }
+    }
+
+    // Returns when the first gold medal among `medals` was fetched, if any
+    // gold medal came from a live fetch.
+    fn earliest_gold_fetched(medals: &[Medal]) -> Option<SystemTime> {
+        medals
+            .iter()
+            .filter(|m| m.r#type == Type::Gold)
+            .filter_map(|m| m.fetched_at)
+            .min()
+    }
+
+    // Maps each event to the country that won its gold medal, or `None` if
+    // no gold was awarded. Shared golds go to the first one listed. Medals
+    // from untitled events are skipped.
+    fn winners_by_event(medals: &[Medal]) -> HashMap<String, Option<String>> {
+        let mut winners = HashMap::new();
+        for medal in medals {
+            let Some(event) = &medal.event else { continue };
+            let winner = winners.entry(event.clone()).or_insert(None);
+            if winner.is_none() && medal.r#type == Type::Gold {
+                *winner = Some(medal.country.clone());
+            }
+        }
+        winners
+    }
+
+    // Returns (event, country) for every event whose gold, silver and bronze
+    // all went to the same country, in the order the events appear. Untitled
+    // events are skipped.
+    fn count_perfect_podiums(medals: &[Medal]) -> Vec<(String, String)> {
+        let mut events = vec![];
+        let mut by_event: HashMap<&str, Vec<&Medal>> = HashMap::new();
+        for medal in medals {
+            let Some(event) = medal.event.as_deref() else {
+                continue;
+            };
+            by_event
+                .entry(event)
+                .or_insert_with(|| {
+                    events.push(event);
+                    vec![]
+                })
+                .push(medal);
+        }
+
+        events
+            .into_iter()
+            .filter_map(|event| {
+                let podium = &by_event[event];
+                let country = &podium[0].country;
+                let sweep = podium.iter().all(|m| &m.country == country)
+                    && [Type::Gold, Type::Silver, Type::Bronze]
+                        .iter()
+                        .all(|t| podium.iter().any(|m| &m.r#type == t));
+                sweep.then(|| (event.to_string(), country.clone()))
+            })
+            .collect()
+    }
+
+    fn medals_by_gender(medals: &[Medal]) -> HashMap<Gender, Vec<&Medal>> {
+        let mut by_gender = HashMap::new();
+        for medal in medals {
+            by_gender
+                .entry(medal.gender)
+                .or_insert_with(Vec::new)
+                .push(medal);
+        }
+        by_gender
+    }
+
+    // A ranked table as returned by `create_table`.
+    type Table = Vec<(String, usize, usize, usize)>;
+
+    // Returns the (men, women) medal tables. Mixed and unknown events count
+    // towards neither; `combined_table` includes them.
+    fn split_by_gender(medals: &[Medal]) -> (Table, Table) {
+        let table_for =
+            |gender| super::create_table(medals.iter().filter(|m| m.gender == gender).cloned());
+        (table_for(Gender::Men), table_for(Gender::Women))
+    }
+
+    fn combined_table(medals: &[Medal]) -> Table {
+        super::create_table(medals.to_vec())
+    }
+
+    #[derive(Debug, PartialEq, Eq)]
+    enum ValidationError {
+        DuplicateMedal { event: String, r#type: Type },
+        EmptyCountry { event: Option<String> },
+        EmptyEvent,
+    }
+
+    // Checks that no event awards a medal type more than once, that every
+    // medal names its country and that event titles, where given, are not
+    // empty. Returns an empty list if valid.
+    fn validate_medals(medals: &[Medal]) -> Vec<ValidationError> {
+        let mut errors = vec![];
+        let mut podiums: HashMap<&str, [usize; 3]> = HashMap::new();
+        for medal in medals {
+            if medal.event.as_deref() == Some("") {
+                errors.push(ValidationError::EmptyEvent);
+            }
+            if medal.country.is_empty() {
+                errors.push(ValidationError::EmptyCountry {
+                    event: medal.event.clone(),
+                });
+            }
+
+            // Untitled events can't be told apart, so their medals aren't
+            // checked for duplicates.
+            let Some(event) = medal.event.as_deref() else {
+                continue;
+            };
+            let slot = match medal.r#type {
+                Type::Gold => 0,
+                Type::Silver => 1,
+                Type::Bronze => 2,
+            };
+            let count = &mut podiums.entry(event).or_default()[slot];
+            *count += 1;
+            if *count == 2 {
+                errors.push(ValidationError::DuplicateMedal {
+                    event: event.to_string(),
+                    r#type: medal.r#type.clone(),
+                });
+            }
+        }
+        errors
+    }
+
+    // Like `parse_medals_from_reader`, for a document already in memory, e.g.
+    // from `reqwest::blocking::Response::bytes()`.
+    fn parse_medals_from_bytes(bytes: &[u8]) -> Result<Vec<Medal>, ParseError> {
+        let json: Value = serde_json::from_slice(bytes)?;
+        super::parse_medals(&json)
+    }
+
+    // Award-level keys `parse_medals_strict` accepts. Only the award object
+    // itself is checked: the participant subtree differs between athletes and
+    // teams and carries presentation data (thumbnails, links), so whitelisting
+    // it would need a per-participant-type schema.
+    const AWARD_FIELDS: [&str; 4] = ["__typename", "medalType", "rank", "participant"];
+
+    // Like `super::parse_medals`, but fails with `ParseError::UnexpectedField`
+    // on any award key outside `AWARD_FIELDS`, so that schema additions are
+    // noticed and the parser can be updated.
+    fn parse_medals_strict(json: &Value) -> Result<Vec<Medal>, ParseError> {
+        super::validate_schema(json)?;
+        super::stream_awards(json)
+            .map(|item| {
+                let (event, award) = item?;
+                let unexpected = award
+                    .as_object()
+                    .into_iter()
+                    .flat_map(|fields| fields.keys())
+                    .find(|key| !AWARD_FIELDS.contains(&key.as_str()));
+                match unexpected {
+                    Some(key) => Err(ParseError::UnexpectedField(key.clone())),
+                    None => super::parse_award(event, award),
+                }
+            })
+            .collect()
+    }
+
+    // Serves `responses` as (status line, JSON body) pairs to successive
+    // requests on a local port and returns the URL to fetch them from.
+    fn serve(responses: Vec<(&'static str, &'static str)>) -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/athletics.json", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for ((status, body), stream) in responses.into_iter().zip(listener.incoming()) {
+                let mut stream = stream.unwrap();
+                let mut line = String::new();
+                let mut reader = BufReader::new(&stream);
+                while reader.read_line(&mut line).unwrap() > 2 {
+                    line.clear();
+                }
+                write!(
+                    stream,
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                )
+                .unwrap();
+            }
+        });
+
+        Url::from_str(&url).unwrap()
     }
 
     #[test]
@@ -231,24 +721,671 @@ mod tests {
         assert_eq!(1, 1);
     }
 
+    #[test]
+    fn test_rank_of() {
+        let projection = Projection(vec![
+            ("Kenya".to_string(), MedalCount { g: 4, s: 4, b: 2 }),
+            ("Jamaica".to_string(), MedalCount { g: 1, s: 3, b: 5 }),
+            ("Italy".to_string(), MedalCount { g: 1, s: 0, b: 0 }),
+        ]);
+
+        assert_eq!(projection.rank_of("Kenya"), Some(1));
+        assert_eq!(projection.rank_of("Jamaica"), Some(2));
+        assert_eq!(projection.rank_of("Italy"), Some(3));
+        assert_eq!(projection.rank_of("Norway"), None);
+    }
+
+    #[test]
+    fn test_top_n() {
+        let projection = Projection(vec![
+            ("Kenya".to_string(), MedalCount { g: 4, s: 4, b: 2 }),
+            ("Jamaica".to_string(), MedalCount { g: 4, s: 1, b: 4 }),
+            ("Italy".to_string(), MedalCount { g: 5, s: 0, b: 0 }),
+        ]);
+
+        assert_eq!(projection.top_n(2), &projection.get()[..2]);
+        assert_eq!(projection.top_n(2), projection.top_n(2));
+        assert_eq!(projection.top_n(0), &[]);
+        assert_eq!(projection.top_n(10), &projection.get()[..]);
+        assert_eq!(projection.get().len(), 3);
+    }
+
+    #[test]
+    fn test_dominance_score() {
+        let count = MedalCount { g: 4, s: 4, b: 2 };
+
+        assert_eq!(count.dominance_score(3.0, 2.0, 1.0), 22.0);
+        assert_eq!(count.dominance_score(1.0, 1.0, 1.0), 10.0);
+        assert_eq!(count.dominance_score(1.0, 0.0, 0.0), 4.0);
+        assert_eq!(count.dominance_score(0.5, 0.25, 0.0), 3.0);
+        assert_eq!(MedalCount::default().dominance_score(3.0, 2.0, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_ratio_gold_to_total() {
+        assert_eq!(MedalCount { g: 3, s: 0, b: 0 }.ratio_gold_to_total(), 1.0);
+        assert_eq!(MedalCount { g: 0, s: 2, b: 5 }.ratio_gold_to_total(), 0.0);
+        assert_eq!(MedalCount { g: 1, s: 2, b: 1 }.ratio_gold_to_total(), 0.25);
+        assert_eq!(MedalCount::default().ratio_gold_to_total(), 0.0);
+    }
+
+    #[test]
+    fn test_olympic_medal_value() {
+        let usa = MedalCount { g: 7, s: 12, b: 7 };
+        let kenya = MedalCount { g: 4, s: 4, b: 2 };
+        let jamaica = MedalCount { g: 4, s: 1, b: 4 };
+
+        assert_eq!(olympic_medal_value(&usa), 52);
+        assert_eq!(olympic_medal_value(&kenya), 22);
+        assert_eq!(olympic_medal_value(&jamaica), 18);
+        assert_eq!(olympic_medal_value(&MedalCount::default()), 0);
+        assert_eq!(
+            usa.olympic_medal_value() as f64,
+            usa.dominance_score(3.0, 2.0, 1.0)
+        );
+    }
+
+    #[test]
+    fn test_refresh() -> Result<(), Box<dyn std::error::Error>> {
+        let url = serve(vec![
+            ("200 OK", r#"{"version": 1}"#),
+            ("200 OK", r#"{"version": 1}"#),
+            ("200 OK", r#"{"version": 2}"#),
+        ]);
+        let mut db = AthleticsDb::from_url(url)?;
+
+        assert!(!db.refresh()?);
+        assert!(db.refresh()?);
+        assert_eq!(db.0.json["version"], 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_discipline_metadata() -> Result<(), Box<dyn std::error::Error>> {
+        let url = Url::from_str(ATHLETICS_URL)?;
+
+        let json: Value = serde_json::from_str(ATHLETICS_JSON)?;
+        let db = AthleticsDb(AthleticsDbInner {
+            url: url.clone(),
+            json,
+        });
+        assert_eq!(
+            db.discipline_metadata(),
+            DisciplineMetadata {
+                name: "Athletics".to_string(),
+                start_date: None,
+                end_date: None,
+                event_count: 48,
+            }
+        );
+
+        let json = serde_json::json!({ "pageProps": { "gameDiscipline": {
+            "title": "Swimming",
+            "startDate": "2021-07-24",
+            "endDate": "2021-08-01",
+            "events": [{ "awards": [] }, { "awards": [] }]
+        } } });
+        let db = AthleticsDb(AthleticsDbInner { url, json });
+        assert_eq!(
+            db.discipline_metadata(),
+            DisciplineMetadata {
+                name: "Swimming".to_string(),
+                start_date: Some("2021-07-24".to_string()),
+                end_date: Some("2021-08-01".to_string()),
+                event_count: 2,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_medals_from_local_server() -> Result<(), Box<dyn std::error::Error>> {
+        let url = serve(vec![("200 OK", ATHLETICS_JSON)]);
+        let before = SystemTime::now();
+        let medals = super::fetch_medals(url.as_str(), FETCH_TIMEOUT)?;
+        assert_eq!(medals.len(), 144);
+        assert!(medals.iter().all(|m| m.fetched_at.unwrap() >= before));
+
+        let table = super::create_table(medals);
+        assert_eq!(table[0], ("United States of America".to_string(), 7, 12, 7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_url_from_local_server() -> Result<(), Box<dyn std::error::Error>> {
+        let url = serve(vec![("200 OK", ATHLETICS_JSON)]);
+        let db = AthleticsDb::from_url(url)?;
+        let top = db.get_medals_per_country()?.take(1);
+
+        assert_eq!(
+            top.get(),
+            &vec![(
+                "United States of America".to_string(),
+                MedalCount { g: 7, s: 12, b: 7 }
+            )]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_error_status() {
+        let url = serve(vec![
+            ("404 Not Found", "{}"),
+            ("500 Internal Server Error", "{}"),
+            ("404 Not Found", "{}"),
+            ("500 Internal Server Error", "{}"),
+        ]);
+
+        assert!(super::fetch_medals(url.as_str(), FETCH_TIMEOUT).is_err());
+        assert!(super::fetch_medals(url.as_str(), FETCH_TIMEOUT).is_err());
+        assert!(AthleticsDb::from_url(url.clone()).is_err());
+        assert!(AthleticsDb::from_url(url).is_err());
+    }
+
+    // Accepts connections on a local port but never answers them.
+    fn serve_nothing() -> Url {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/athletics.json", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            let _connections: Vec<_> = listener.incoming().collect();
+        });
+
+        Url::from_str(&url).unwrap()
+    }
+
+    #[test]
+    fn test_fetch_timeout() {
+        let url = serve_nothing();
+        let err = super::fetch_medals(url.as_str(), Duration::from_millis(200)).unwrap_err();
+
+        assert!(err
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(reqwest::Error::is_timeout));
+    }
+
+    #[test]
+    fn test_fetch_malformed_json() {
+        let url = serve(vec![
+            ("200 OK", r#"{"pageProps": "#),
+            ("200 OK", r#"{"pageProps": "#),
+        ]);
+
+        assert!(super::fetch_medals(url.as_str(), FETCH_TIMEOUT).is_err());
+        assert!(AthleticsDb::from_url(url).is_err());
+    }
+
+    #[test]
+    fn test_validate_schema() -> Result<(), Box<dyn std::error::Error>> {
+        let json: Value = serde_json::from_str(ATHLETICS_JSON)?;
+        assert_eq!(super::validate_schema(&json), Ok(()));
+
+        let missing = |keys: &[&str]| {
+            Err(SchemaError {
+                missing: keys.iter().map(|k| k.to_string()).collect(),
+            })
+        };
+        assert_eq!(
+            super::validate_schema(&serde_json::json!({})),
+            missing(&["pageProps"])
+        );
+        assert_eq!(
+            super::validate_schema(&serde_json::json!({ "pageProps": {} })),
+            missing(&["pageProps.gameDiscipline"])
+        );
+        assert_eq!(
+            super::validate_schema(&serde_json::json!({
+                "pageProps": { "gameDiscipline": { "events": {} } }
+            })),
+            missing(&["pageProps.gameDiscipline.events"])
+        );
+        assert_eq!(
+            super::validate_schema(&serde_json::json!({
+                "pageProps": { "gameDiscipline": { "events": [{ "awards": [] }, {}, {}] } }
+            })),
+            missing(&[
+                "pageProps.gameDiscipline.events[1].awards",
+                "pageProps.gameDiscipline.events[2].awards"
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fetch_unexpected_schema() {
+        let url = serve(vec![("200 OK", r#"{"pageProps": {}}"#)]);
+        let err = super::fetch_medals(url.as_str(), FETCH_TIMEOUT).unwrap_err();
+
+        assert_eq!(err.to_string(), "unexpected medal JSON schema");
+        assert_eq!(
+            err.source().map(|source| source.to_string()),
+            Some("missing keys in medal JSON: pageProps.gameDiscipline".to_string())
+        );
+    }
+
+    #[test]
+    fn test_stream_medals_in_document_order() -> Result<(), Box<dyn std::error::Error>> {
+        let json: Value = serde_json::from_str(ATHLETICS_JSON)?;
+        let first = super::stream_medals(&json)
+            .take(6)
+            .map(|medal| medal.map(|m| (m.r#type, m.country)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        assert_eq!(
+            first,
+            vec![
+                (Type::Gold, "Kenya".to_string()),
+                (Type::Silver, "Great Britain".to_string()),
+                (Type::Bronze, "Netherlands".to_string()),
+                (Type::Gold, "Poland".to_string()),
+                (Type::Silver, "Dominican Republic".to_string()),
+                (Type::Bronze, "United States of America".to_string()),
+            ]
+        );
+
+        let table = super::create_table(super::stream_medals(&json).map(Result::unwrap));
+        assert_eq!(table[0], ("United States of America".to_string(), 7, 12, 7));
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_medals_errors() {
+        let json = serde_json::json!({ "pageProps": { "gameDiscipline": { "events": [{
+            "title": "Women's 1500m",
+            "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Kenya" } },
+                { "medalType": "TIN", "participant": { "title": "Kenya" } },
+                { "participant": { "title": "Kenya" } },
+                { "medalType": "BRONZE", "participant": {} }
+            ]
+        }] } } });
+        let errors: Vec<_> = super::stream_medals(&json)
+            .filter_map(Result::err)
+            .map(|err| err.to_string())
+            .collect();
+
+        assert_eq!(
+            errors,
+            vec![
+                r#"unknown medal type "TIN""#,
+                "award is missing its medalType",
+                "award is missing its country",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stream_medals_missing_arrays() {
+        let schema_errors = |json: Value| -> Vec<SchemaError> {
+            super::stream_medals(&json)
+                .filter_map(|medal| match medal {
+                    Err(ParseError::Schema(err)) => Some(err),
+                    _ => None,
+                })
+                .collect()
+        };
+        let missing = |key: &str| SchemaError {
+            missing: vec![key.to_string()],
+        };
+
+        assert_eq!(
+            schema_errors(serde_json::json!({})),
+            vec![missing("pageProps.gameDiscipline.events")]
+        );
+        assert_eq!(
+            schema_errors(
+                serde_json::json!({ "pageProps": { "gameDiscipline": { "events": [
+                { "awards": [{ "medalType": "GOLD", "participant": { "title": "Kenya" } }] },
+                { "awards": {} }
+            ] } } })
+            ),
+            vec![missing("pageProps.gameDiscipline.events[1].awards")]
+        );
+    }
+
+    #[test]
+    fn test_parse_medals_from_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let bytes = br#"{ "pageProps": { "gameDiscipline": { "events": [
+            { "title": "Women's 1500m", "gender": "Women", "awards": [
+                { "medalType": "GOLD", "participant": { "countryObject": { "name": "Kenya" } } },
+                { "medalType": "SILVER", "participant": { "title": "Great Britain" } }
+            ] }
+        ] } } }"#;
+        let medals = parse_medals_from_bytes(bytes)?;
+
+        assert_eq!(
+            medals
+                .iter()
+                .map(|m| (m.r#type.clone(), m.country.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(Type::Gold, "Kenya"), (Type::Silver, "Great Britain")]
+        );
+        assert_eq!(
+            parse_medals_from_bytes(ATHLETICS_JSON.as_bytes())?.len(),
+            144
+        );
+        assert!(matches!(
+            parse_medals_from_bytes(b"{\"pageProps\": "),
+            Err(ParseError::Json(_))
+        ));
+        assert!(matches!(
+            parse_medals_from_bytes(b"{}"),
+            Err(ParseError::Schema(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_medals_from_reader() -> Result<(), Box<dyn std::error::Error>> {
+        let file = File::open(concat!(env!("CARGO_MANIFEST_DIR"), "/athletics.json"))?;
+        let medals = super::parse_medals_from_reader(BufReader::new(file))?;
+        assert_eq!(medals.len(), 144);
+        assert!(medals.iter().all(|m| m.fetched_at.is_none()));
+
+        assert!(matches!(
+            super::parse_medals_from_reader(&b"{\"pageProps\": "[..]),
+            Err(ParseError::Json(_))
+        ));
+        assert!(matches!(
+            super::parse_medals_from_reader(&b"{\"pageProps\": {}}"[..]),
+            Err(ParseError::Schema(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_winners_by_event() -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::json!({ "pageProps": { "gameDiscipline": { "events": [
+            { "title": "Women's 1500m", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Kenya" } },
+                { "medalType": "SILVER", "participant": { "title": "Great Britain" } }
+            ] },
+            { "title": "Men's Marathon", "awards": [
+                { "medalType": "SILVER", "participant": { "title": "Netherlands" } },
+                { "medalType": "BRONZE", "participant": { "title": "Belgium" } }
+            ] },
+            { "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Ethiopia" } }
+            ] }
+        ] } } });
+        let medals = super::stream_medals(&json).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(medals[4].event, None);
+        let winners = winners_by_event(&medals);
+
+        assert_eq!(winners.len(), 2);
+        assert_eq!(winners["Women's 1500m"], Some("Kenya".to_string()));
+        assert_eq!(winners["Men's Marathon"], None);
+
+        let medals = super::parse_medals_from_reader(ATHLETICS_JSON.as_bytes())?;
+        let winners = winners_by_event(&medals);
+        assert_eq!(winners.len(), 48);
+        assert_eq!(winners["Women's 100m"], Some("Jamaica".to_string()));
+        assert_eq!(winners["Men's High Jump"], Some("Qatar".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_perfect_podiums() -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::json!({ "pageProps": { "gameDiscipline": { "events": [
+            { "title": "Women's 100m", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Jamaica" } },
+                { "medalType": "SILVER", "participant": { "title": "Jamaica" } },
+                { "medalType": "BRONZE", "participant": { "title": "Jamaica" } }
+            ] },
+            { "title": "Men's 100m", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Italy" } },
+                { "medalType": "SILVER", "participant": { "title": "United States of America" } },
+                { "medalType": "BRONZE", "participant": { "title": "Canada" } }
+            ] },
+            { "title": "Men's 200m", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Canada" } },
+                { "medalType": "GOLD", "participant": { "title": "Canada" } }
+            ] }
+        ] } } });
+        let medals = super::stream_medals(&json).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            count_perfect_podiums(&medals),
+            vec![("Women's 100m".to_string(), "Jamaica".to_string())]
+        );
+
+        let medals = super::parse_medals_from_reader(ATHLETICS_JSON.as_bytes())?;
+        assert_eq!(
+            count_perfect_podiums(&medals),
+            vec![("Women's 100m".to_string(), "Jamaica".to_string())]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_medals() -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::json!({ "pageProps": { "gameDiscipline": { "events": [
+            { "title": "Women's 100m", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Jamaica" } },
+                { "medalType": "SILVER", "participant": { "title": "Jamaica" } },
+                { "medalType": "BRONZE", "participant": { "title": "Jamaica" } }
+            ] }
+        ] } } });
+        let medals = super::stream_medals(&json).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(validate_medals(&medals), vec![]);
+
+        let json = serde_json::json!({ "pageProps": { "gameDiscipline": { "events": [
+            { "title": "Men's 200m", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Canada" } },
+                { "medalType": "GOLD", "participant": { "title": "Kenya" } },
+                { "medalType": "GOLD", "participant": { "title": "Italy" } },
+                { "medalType": "BRONZE", "participant": { "title": "" } }
+            ] },
+            { "title": "", "awards": [
+                { "medalType": "SILVER", "participant": { "title": "Kenya" } }
+            ] }
+        ] } } });
+        let medals = super::stream_medals(&json).collect::<Result<Vec<_>, _>>()?;
+        assert_eq!(
+            validate_medals(&medals),
+            vec![
+                ValidationError::DuplicateMedal {
+                    event: "Men's 200m".to_string(),
+                    r#type: Type::Gold
+                },
+                ValidationError::EmptyCountry {
+                    event: Some("Men's 200m".to_string())
+                },
+                ValidationError::EmptyEvent,
+            ]
+        );
+
+        let medals = super::parse_medals_from_reader(ATHLETICS_JSON.as_bytes())?;
+        assert_eq!(
+            validate_medals(&medals),
+            vec![ValidationError::DuplicateMedal {
+                event: "Men's High Jump".to_string(),
+                r#type: Type::Gold
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_valid_medal_type() {
+        assert!(super::is_valid_medal_type("GOLD"));
+        assert!(super::is_valid_medal_type("SILVER"));
+        assert!(super::is_valid_medal_type("BRONZE"));
+        assert!(!super::is_valid_medal_type("TIN"));
+        assert!(!super::is_valid_medal_type("gold"));
+        assert!(!super::is_valid_medal_type("Gold"));
+        assert!(!super::is_valid_medal_type(" GOLD"));
+        assert!(!super::is_valid_medal_type(""));
+    }
+
+    #[test]
+    fn test_medals_by_gender() -> Result<(), Box<dyn std::error::Error>> {
+        let json = serde_json::json!({ "pageProps": { "gameDiscipline": { "events": [
+            { "title": "Women's 100m", "gender": "Women", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Jamaica" } },
+                { "medalType": "SILVER", "participant": { "title": "Jamaica" } }
+            ] },
+            { "title": "Men's 100m", "gender": "Men", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Italy" } }
+            ] },
+            { "title": "Exhibition", "awards": [
+                { "medalType": "GOLD", "participant": { "title": "Kenya" } }
+            ] }
+        ] } } });
+        let medals = super::stream_medals(&json).collect::<Result<Vec<_>, _>>()?;
+        let by_gender = medals_by_gender(&medals);
+
+        let countries = |gender| -> Vec<&str> {
+            by_gender[&gender]
+                .iter()
+                .map(|m| m.country.as_str())
+                .collect()
+        };
+        assert_eq!(countries(Gender::Women), vec!["Jamaica", "Jamaica"]);
+        assert_eq!(countries(Gender::Men), vec!["Italy"]);
+        assert_eq!(countries(Gender::Unknown), vec!["Kenya"]);
+        assert!(!by_gender.contains_key(&Gender::Mixed));
+
+        let medals = super::parse_medals_from_reader(ATHLETICS_JSON.as_bytes())?;
+        let by_gender = medals_by_gender(&medals);
+        assert_eq!(by_gender[&Gender::Men].len(), 72);
+        assert_eq!(by_gender[&Gender::Women].len(), 69);
+        assert_eq!(by_gender[&Gender::Mixed].len(), 3);
+        assert!(!by_gender.contains_key(&Gender::Unknown));
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_by_gender() -> Result<(), Box<dyn std::error::Error>> {
+        let medals = super::parse_medals_from_reader(ATHLETICS_JSON.as_bytes())?;
+        let (men, women) = split_by_gender(&medals);
+        let combined = combined_table(&medals);
+        let mixed =
+            super::create_table(medals.iter().filter(|m| m.gender == Gender::Mixed).cloned());
+
+        let total = |table: &[(String, usize, usize, usize)]| -> usize {
+            table.iter().map(|e| e.1 + e.2 + e.3).sum()
+        };
+        assert_eq!(total(&men), 72);
+        assert_eq!(total(&women), 69);
+        assert_eq!(total(&combined), 144);
+
+        for (country, gold, silver, bronze) in &combined {
+            let mut sum = (0, 0, 0);
+            for table in [&men, &women, &mixed] {
+                if let Some(e) = table.iter().find(|e| &e.0 == country) {
+                    sum = (sum.0 + e.1, sum.1 + e.2, sum.2 + e.3);
+                }
+            }
+            assert_eq!(sum, (*gold, *silver, *bronze), "{}", country);
+        }
+        assert_eq!(
+            combined[0],
+            ("United States of America".to_string(), 7, 12, 7)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_medals_strict() -> Result<(), Box<dyn std::error::Error>> {
+        let json: Value = serde_json::from_str(ATHLETICS_JSON)?;
+        assert_eq!(parse_medals_strict(&json)?.len(), 144);
+
+        let json = serde_json::json!({ "pageProps": { "gameDiscipline": { "events": [
+            { "title": "Women's 100m", "awards": [
+                { "medalType": "GOLD", "rank": {}, "participant": { "title": "Jamaica" } },
+                { "medalType": "SILVER", "recordType": "OR", "participant": { "title": "Jamaica" } }
+            ] }
+        ] } } });
+        assert!(matches!(
+            parse_medals_strict(&json),
+            Err(ParseError::UnexpectedField(key)) if key == "recordType"
+        ));
+        assert_eq!(super::stream_medals(&json).count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_earliest_gold_fetched() {
+        let t0 = SystemTime::UNIX_EPOCH + Duration::from_secs(1_627_000_000);
+        let medal = |r#type, fetched_at| Medal {
+            r#type,
+            country: "Kenya".to_string(),
+            event: Some("Women's 1500m".to_string()),
+            gender: Gender::Women,
+            fetched_at,
+        };
+
+        assert_eq!(earliest_gold_fetched(&[]), None);
+        assert_eq!(
+            earliest_gold_fetched(&[medal(Type::Gold, None), medal(Type::Silver, Some(t0))]),
+            None
+        );
+        assert_eq!(
+            earliest_gold_fetched(&[
+                medal(Type::Gold, Some(t0 + Duration::from_secs(60))),
+                medal(Type::Bronze, Some(t0 - Duration::from_secs(60))),
+                medal(Type::Gold, None),
+                medal(Type::Gold, Some(t0)),
+            ]),
+            Some(t0)
+        );
+    }
+
+    fn arb_medal() -> impl Strategy<Value = Medal> {
+        (0..3, "[A-E]{1,2}", "[a-c]").prop_map(|(i, country, event)| {
+            let r#type = match i {
+                0 => Type::Gold,
+                1 => Type::Silver,
+                _ => Type::Bronze,
+            };
+            Medal {
+                r#type,
+                country,
+                event: Some(event),
+                gender: Gender::Unknown,
+                fetched_at: None,
+            }
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_create_table_invariants(medals in prop::collection::vec(arb_medal(), 0..200)) {
+            let table = super::create_table(medals.clone());
+
+            let mut countries: Vec<_> = medals.iter().map(|m| &m.country).collect();
+            countries.sort();
+            countries.dedup();
+            prop_assert_eq!(table.len(), countries.len());
+
+            for (country, gold, silver, bronze) in &table {
+                let won = medals.iter().filter(|m| &m.country == country).count();
+                prop_assert_eq!(gold + silver + bronze, won);
+            }
+            prop_assert_eq!(table.iter().map(|e| e.1 + e.2 + e.3).sum::<usize>(), medals.len());
+
+            for pair in table.windows(2) {
+                prop_assert!((pair[0].1, pair[0].2, pair[0].3) >= (pair[1].1, pair[1].2, pair[1].3));
+            }
+        }
+    }
+
     #[test]
     fn new_test() -> Result<(), Box<dyn std::error::Error>> {
-        let url =
-            "https://raw.githubusercontent.com/sekaiser/rust-sample-parse-json/main/athletics.json";
-        let db = AthleticsDb::from_url(Url::from_str(url)?)?;
+        let db = AthleticsDb::from_url(Url::from_str(ATHLETICS_URL)?)?;
 
-        let mut last_top5 = Projection::empty();
+        let mut last = Projection::empty();
         loop {
             let medals_per_country = db.get_medals_per_country()?;
-            let top5 = medals_per_country.take(5);
+            let top5 = medals_per_country.top_n(5);
 
-            if top5 != last_top5 {
-                for e in top5.get() {
+            if top5 != last.top_n(5) {
+                for e in top5 {
                     println!("{} {:?}", e.0, e.1);
                 }
-
-                last_top5 = top5;
             }
+
+            last = medals_per_country;
             thread::sleep(time::Duration::from_secs(2));
         }
     }